@@ -0,0 +1,793 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::collections::HashSet;
+
+use vulkano::instance::{
+    Instance,
+    InstanceExtensions,
+    layers_list,
+    ApplicationInfo,
+    Version,
+    PhysicalDevice,
+    Features,
+};
+use vulkano::instance::debug::{DebugCallback, MessageTypes};
+use vulkano::device::{Device, DeviceExtensions, Queue};
+use vulkano::swapchain::{
+    Surface,
+    Capabilities,
+    ColorSpace,
+    SupportedPresentModes, PresentMode,
+    Swapchain,
+    CompositeAlpha,
+    acquire_next_image,
+    AcquireError,
+};
+use vulkano::format::Format;
+use vulkano::image::ImageUsage;
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::image::swapchain::SwapchainImage;
+use vulkano::sync::{SharingMode, GpuFuture, FlushError, FenceSignalFuture};
+use vulkano::framebuffer::{RenderPassAbstract, FramebufferAbstract, Framebuffer, Subpass};
+use vulkano::pipeline::GraphicsPipeline;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::pipeline::vertex::{BufferlessDefinition, BufferlessVertices};
+use vulkano::command_buffer::{AutoCommandBuffer, AutoCommandBufferBuilder, DynamicState};
+
+/// How many frames may be queued up for the GPU at once.
+const MAX_FRAMES_IN_FLIGHT: usize = 2;
+
+const VALIDATION_LAYERS: &[&str] =  &[
+    "VK_LAYER_LUNARG_standard_validation"
+];
+
+// MoltenVK doesn't have any layers by default
+#[cfg(all(debug_assertions, not(target_os = "macos")))]
+const ENABLE_VALIDATION_LAYERS: bool = true;
+#[cfg(any(not(debug_assertions), target_os = "macos"))]
+const ENABLE_VALIDATION_LAYERS: bool = false;
+
+mod vertex_shader {
+    #[derive(VulkanoShader)]
+    #[ty = "vertex"]
+    #[src = "
+        #version 450
+
+        layout(location = 0) out vec3 fragColor;
+
+        vec2 positions[3] = vec2[](
+            vec2(0.0, -0.5),
+            vec2(0.5, 0.5),
+            vec2(-0.5, 0.5)
+        );
+
+        vec3 colors[3] = vec3[](
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(0.0, 0.0, 1.0)
+        );
+
+        void main() {
+            gl_Position = vec4(positions[gl_VertexIndex], 0.0, 1.0);
+            fragColor = colors[gl_VertexIndex];
+        }
+    "]
+    struct Dummy;
+}
+
+mod fragment_shader {
+    #[derive(VulkanoShader)]
+    #[ty = "fragment"]
+    #[src = "
+        #version 450
+
+        layout(location = 0) in vec3 fragColor;
+
+        layout(location = 0) out vec4 outColor;
+
+        void main() {
+            outColor = vec4(fragColor, 1.0);
+        }
+    "]
+    struct Dummy;
+}
+
+/// The fixed-function state of our single graphics pipeline, spelled out so it
+/// can be stored on the renderer (vulkano's builder returns this concrete,
+/// unwieldy type rather than a `dyn` trait object).
+type ConcreteGraphicsPipeline = GraphicsPipeline<
+    BufferlessDefinition,
+    Box<dyn vulkano::descriptor::pipeline_layout::PipelineLayoutAbstract + Send + Sync>,
+    Arc<dyn RenderPassAbstract + Send + Sync>,
+>;
+
+/// A submitted frame's completion fence, boxed so it can be stored without
+/// naming the concrete (and otherwise unspeakable) present/execute future chain.
+type FrameFuture = FenceSignalFuture<Box<dyn GpuFuture>>;
+
+/// Required device extensions
+fn device_extensions() -> DeviceExtensions {
+    DeviceExtensions {
+        khr_swapchain: true,
+        .. vulkano::device::DeviceExtensions::none()
+    }
+}
+
+/// Minimum validation-layer message severity forwarded to the `log` crate.
+///
+/// Ordered least to most severe so a threshold can be compared with `>=`;
+/// messages below it are dropped before `log`'s own level filtering (e.g.
+/// `RUST_LOG`) ever sees them.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum ValidationSeverity {
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Default for ValidationSeverity {
+    fn default() -> Self {
+        ValidationSeverity::Warning
+    }
+}
+
+impl ValidationSeverity {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => ValidationSeverity::Trace,
+            1 => ValidationSeverity::Debug,
+            2 => ValidationSeverity::Info,
+            3 => ValidationSeverity::Warning,
+            _ => ValidationSeverity::Error,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct QueueFamilyIndices {
+    graphics_family: i32,
+    present_family: i32,
+}
+impl QueueFamilyIndices {
+    fn new() -> Self {
+        Self { graphics_family: -1, present_family: -1 }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.graphics_family >= 0 && self.present_family >= 0
+    }
+}
+
+/// The Vulkan core of the application: instance, device, swapchain, the
+/// single hardcoded-triangle pipeline and the per-frame synchronization
+/// needed to draw with it. Generic over the surface's window type so it can
+/// be embedded under any winit window, or a headless off-screen surface.
+pub struct Renderer<W> {
+    instance: Arc<Instance>,
+    debug_callback: Option<DebugCallback>,
+    surface: Arc<Surface<W>>,
+
+    physical_device_index: usize, // can't store PhysicalDevice directly (lifetime issues)
+    queue_family_indices: QueueFamilyIndices,
+    device: Arc<Device>,
+
+    graphics_queue: Arc<Queue>,
+    present_queue: Arc<Queue>,
+
+    /// Fallback swapchain extent, used only when the platform can't report
+    /// the surface's current size; kept up to date via `recreate_swapchain`.
+    window_extent: [u32; 2],
+
+    swap_chain: Arc<Swapchain<W>>,
+    swap_chain_images: Vec<Arc<SwapchainImage<W>>>,
+    swap_chain_image_format: Format,
+    swap_chain_extent: [u32; 2],
+
+    /// Depth buffer, sized to `swap_chain_extent`; rebuilt alongside the
+    /// swapchain on resize.
+    depth_image: Arc<AttachmentImage>,
+
+    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    graphics_pipeline: Arc<ConcreteGraphicsPipeline>,
+
+    swap_chain_framebuffers: Vec<Arc<dyn FramebufferAbstract + Send + Sync>>,
+    command_buffers: Vec<Arc<AutoCommandBuffer>>,
+
+    /// Set when the swapchain must be rebuilt before the next frame is drawn
+    /// (window resize, or the previous frame reported it out of date).
+    /// Coalesces multiple resize events into a single rebuild.
+    recreate_swap_chain_needed: bool,
+
+    /// One completion fence per frame-in-flight slot.
+    in_flight_fences: Vec<Arc<FrameFuture>>,
+    /// The fence (if any) currently guarding each swapchain image, so a
+    /// reused image can be waited on before it's rendered to again.
+    images_in_flight: Vec<Option<Arc<FrameFuture>>>,
+    /// Index of the frame-in-flight slot used by the current frame.
+    current_frame: usize,
+
+    /// Validation layer messages below this severity are dropped. Shared
+    /// with the debug callback so it can be changed at runtime (e.g. to
+    /// suppress info/debug spam) after construction.
+    min_validation_severity: Arc<AtomicU8>,
+}
+
+impl<W> Renderer<W> {
+    /// Builds the Vulkan instance, then hands it to `build_surface` to create
+    /// the platform surface - the renderer itself has no window-system
+    /// knowledge beyond the extensions it's told to require. `window_extent`
+    /// is used as the swapchain's fallback size on platforms that don't
+    /// report a current surface extent.
+    pub fn new<F>(required_extensions: InstanceExtensions, window_extent: [u32; 2], build_surface: F) -> Self
+    where
+        F: FnOnce(&Arc<Instance>) -> Arc<Surface<W>>,
+    {
+        let instance = Self::create_instance(required_extensions);
+        let min_validation_severity = Arc::new(AtomicU8::new(ValidationSeverity::default() as u8));
+        let debug_callback = Self::setup_debug_callback(&instance, min_validation_severity.clone());
+        let surface = build_surface(&instance);
+
+        let physical_device_index = Self::pick_physical_device(&instance, &surface);
+        let physical_device = PhysicalDevice::from_index(&instance, physical_device_index).unwrap();
+        let queue_family_indices = Self::find_queue_families(&surface, &physical_device);
+
+        let (device, graphics_queue, present_queue) =
+            Self::create_logical_device(&physical_device, queue_family_indices);
+
+        let (swap_chain, swap_chain_images, swap_chain_image_format, swap_chain_extent) = Self::create_swap_chain(
+            &device, &surface, &physical_device, queue_family_indices, window_extent,
+            &graphics_queue, &present_queue, None,
+        );
+
+        let depth_format = Self::find_depth_format(&physical_device);
+        let depth_image = Self::create_depth_resources(&device, depth_format, swap_chain_extent);
+
+        let render_pass = Self::create_render_pass(&device, swap_chain_image_format, depth_format);
+        let graphics_pipeline = Self::create_graphics_pipeline(&device, swap_chain_extent, &render_pass);
+        let swap_chain_framebuffers = Self::create_framebuffers(&render_pass, &swap_chain_images, &depth_image);
+        let command_buffers =
+            Self::create_command_buffers(&device, &graphics_queue, &graphics_pipeline, &swap_chain_framebuffers);
+
+        let in_flight_fences = Self::create_sync_objects(&device);
+        let images_in_flight = vec![None; swap_chain_images.len()];
+
+        Self {
+            instance,
+            debug_callback,
+            surface,
+            physical_device_index,
+            queue_family_indices,
+            device,
+            graphics_queue,
+            present_queue,
+            window_extent,
+            swap_chain,
+            swap_chain_images,
+            swap_chain_image_format,
+            swap_chain_extent,
+            depth_image,
+            render_pass,
+            graphics_pipeline,
+            swap_chain_framebuffers,
+            command_buffers,
+            recreate_swap_chain_needed: false,
+            in_flight_fences,
+            images_in_flight,
+            current_frame: 0,
+            min_validation_severity,
+        }
+    }
+
+    /// The surface this renderer draws into.
+    pub fn surface(&self) -> &Arc<Surface<W>> {
+        &self.surface
+    }
+
+    /// Minimum validation-layer message severity forwarded to the `log` crate.
+    pub fn min_validation_severity(&self) -> ValidationSeverity {
+        ValidationSeverity::from_u8(self.min_validation_severity.load(Ordering::Relaxed))
+    }
+
+    /// Changes the minimum validation-layer message severity at runtime,
+    /// e.g. to suppress info/debug spam once startup has settled down.
+    pub fn set_min_validation_severity(&self, severity: ValidationSeverity) {
+        self.min_validation_severity.store(severity as u8, Ordering::Relaxed);
+    }
+
+    /// Draws and presents a single frame, transparently rebuilding the
+    /// swapchain first if a resize was requested or the previous frame's
+    /// present reported the swapchain out of date.
+    pub fn draw_frame(&mut self) {
+        if self.recreate_swap_chain_needed {
+            self.rebuild_swap_chain();
+            self.recreate_swap_chain_needed = false;
+        }
+
+        let frame = self.current_frame;
+        self.in_flight_fences[frame].wait(None).unwrap();
+
+        let swap_chain = self.swap_chain.clone();
+
+        let (image_index, acquire_future) = match acquire_next_image(swap_chain.clone(), None) {
+            Ok(r) => r,
+            Err(AcquireError::OutOfDate) => {
+                self.recreate_swap_chain_needed = true;
+                return;
+            },
+            Err(err) => panic!("failed to acquire next image: {:?}", err),
+        };
+
+        // MAX_FRAMES_IN_FLIGHT may be less than the swapchain's image count,
+        // so the image we were just handed could still be in use by another
+        // in-flight frame; wait for it before recording over it.
+        if let Some(image_fence) = self.images_in_flight[image_index].clone() {
+            image_fence.wait(None).unwrap();
+        }
+
+        let command_buffer = self.command_buffers[image_index].clone();
+        let graphics_queue = self.graphics_queue.clone();
+        let present_queue = self.present_queue.clone();
+
+        // Only commit to a new fence for this slot once we're actually
+        // submitting: bailing out early on an out-of-date swapchain leaves
+        // the previous (already-signaled) fence in place instead of an
+        // unsignaled one, which would otherwise deadlock the next wait.
+        let future = acquire_future
+            .then_execute(graphics_queue, command_buffer)
+            .unwrap()
+            .then_swapchain_present(present_queue, swap_chain.clone(), image_index)
+            .boxed()
+            .then_signal_fence_and_flush();
+
+        // NOTE: vulkano surfaces a suboptimal swapchain the same way as an
+        // out-of-date one: the present fails with `FlushError::OutOfDate`.
+        let future = match future {
+            Ok(future) => Arc::new(future),
+            Err(FlushError::OutOfDate) => {
+                self.recreate_swap_chain_needed = true;
+                return;
+            },
+            Err(err) => panic!("failed to flush future: {:?}", err),
+        };
+
+        self.images_in_flight[image_index] = Some(future.clone());
+        self.in_flight_fences[frame] = future;
+
+        self.current_frame = (frame + 1) % MAX_FRAMES_IN_FLIGHT;
+    }
+
+    /// Requests that the swapchain be rebuilt at `new_extent` before the next
+    /// frame, e.g. in response to a window resize. Multiple calls before the
+    /// next `draw_frame` coalesce into a single rebuild.
+    pub fn recreate_swapchain(&mut self, new_extent: [u32; 2]) {
+        self.window_extent = new_extent;
+        self.recreate_swap_chain_needed = true;
+    }
+
+    /// Rebuilds the swapchain (and everything that depends on its images) in
+    /// place, e.g. after a window resize or an out-of-date/suboptimal present.
+    fn rebuild_swap_chain(&mut self) {
+        let physical_device = PhysicalDevice::from_index(&self.instance, self.physical_device_index).unwrap();
+
+        let (swap_chain, swap_chain_images, swap_chain_image_format, swap_chain_extent) = Self::create_swap_chain(
+            &self.device, &self.surface, &physical_device, self.queue_family_indices, self.window_extent,
+            &self.graphics_queue, &self.present_queue, Some(self.swap_chain.clone()),
+        );
+
+        self.swap_chain = swap_chain;
+        self.swap_chain_images = swap_chain_images;
+        self.swap_chain_image_format = swap_chain_image_format;
+        self.swap_chain_extent = swap_chain_extent;
+
+        let depth_format = Self::find_depth_format(&physical_device);
+        self.depth_image = Self::create_depth_resources(&self.device, depth_format, self.swap_chain_extent);
+
+        self.swap_chain_framebuffers =
+            Self::create_framebuffers(&self.render_pass, &self.swap_chain_images, &self.depth_image);
+        self.command_buffers = Self::create_command_buffers(
+            &self.device, &self.graphics_queue, &self.graphics_pipeline, &self.swap_chain_framebuffers,
+        );
+        self.images_in_flight = vec![None; self.swap_chain_images.len()];
+    }
+
+    fn create_instance(required_extensions: InstanceExtensions) -> Arc<Instance> {
+        if ENABLE_VALIDATION_LAYERS && !Self::check_validation_layer_support() {
+            panic!("validation layers requested, but not available!")
+        }
+
+        let supported_extensions = InstanceExtensions::supported_by_core()
+            .expect("failed to retrieve supported extensions");
+        println!("Supported extensions: {:?}", supported_extensions);
+
+        let app_info = ApplicationInfo {
+            application_name: Some("Hello Triangle".into()),
+            application_version: Some(Version { major: 1, minor: 0, patch: 0 }),
+            engine_name: Some("No Engine".into()),
+            engine_version: Some(Version { major: 1, minor: 0, patch: 0 }),
+        };
+
+        let mut extensions = required_extensions;
+        if ENABLE_VALIDATION_LAYERS {
+            // TODO!: this should be ext_debug_utils (_report is deprecated), but that doesn't exist yet in vulkano
+            extensions.ext_debug_report = true;
+        }
+
+        if ENABLE_VALIDATION_LAYERS {
+            Instance::new(Some(&app_info), &extensions, VALIDATION_LAYERS.iter().map(|s| *s))
+                .expect("failed to create Vulkan instance")
+        } else {
+            Instance::new(Some(&app_info), &extensions, None)
+                .expect("failed to create Vulkan instance")
+        }
+    }
+
+    fn setup_debug_callback(instance: &Arc<Instance>, min_severity: Arc<AtomicU8>) -> Option<DebugCallback> {
+        if !ENABLE_VALIDATION_LAYERS {
+            return None;
+        }
+
+        let msg_types = MessageTypes {
+            error: true,
+            warning: true,
+            performance_warning: true,
+            information: true,
+            debug: true,
+        };
+
+        DebugCallback::new(instance, msg_types, move |msg| {
+            let severity = if msg.ty.error {
+                ValidationSeverity::Error
+            } else if msg.ty.warning || msg.ty.performance_warning {
+                ValidationSeverity::Warning
+            } else if msg.ty.information {
+                ValidationSeverity::Info
+            } else {
+                ValidationSeverity::Debug
+            };
+
+            if severity < ValidationSeverity::from_u8(min_severity.load(Ordering::Relaxed)) {
+                return;
+            }
+
+            match severity {
+                ValidationSeverity::Error => error!("{:?}: {}", msg.ty, msg.description),
+                ValidationSeverity::Warning => warn!("{:?}: {}", msg.ty, msg.description),
+                ValidationSeverity::Info => debug!("{:?}: {}", msg.ty, msg.description),
+                ValidationSeverity::Debug | ValidationSeverity::Trace => trace!("{:?}: {}", msg.ty, msg.description),
+            }
+        }).ok()
+    }
+
+    fn pick_physical_device(instance: &Arc<Instance>, surface: &Surface<W>) -> usize {
+        PhysicalDevice::enumerate(instance)
+            .position(|device| Self::is_device_suitable(surface, &device))
+            .expect("failed to find a suitable GPU!")
+    }
+
+    fn is_device_suitable(surface: &Surface<W>, device: &PhysicalDevice) -> bool {
+        let indices = Self::find_queue_families(surface, device);
+        let extensions_supported = Self::check_device_extension_support(device);
+
+        let swap_chain_adequate = if extensions_supported {
+                let capabilities = surface.capabilities(*device)
+                    .expect("failed to get surface capabilities");
+                !capabilities.supported_formats.is_empty() &&
+                    capabilities.present_modes.iter().next().is_some()
+            } else {
+                false
+            };
+
+        indices.is_complete() && extensions_supported && swap_chain_adequate
+    }
+
+    fn check_device_extension_support(device: &PhysicalDevice) -> bool {
+        let available_extensions = DeviceExtensions::supported_by_device(*device);
+        let device_extensions = device_extensions();
+        available_extensions.intersection(&device_extensions) == device_extensions
+    }
+
+    fn create_logical_device(
+        physical_device: &PhysicalDevice,
+        indices: QueueFamilyIndices,
+    ) -> (Arc<Device>, Arc<Queue>, Arc<Queue>) {
+        let families = [indices.graphics_family, indices.present_family];
+        use std::iter::FromIterator;
+        let unique_queue_families: HashSet<&i32> = HashSet::from_iter(families.iter());
+
+        let queue_priority = 1.0;
+        let queue_families = unique_queue_families.iter().map(|i| {
+            (physical_device.queue_families().nth(**i as usize).unwrap(), queue_priority)
+        });
+
+        // NOTE: the tutorial recommends passing the validation layers as well
+        // for legacy reasons (if ENABLE_VALIDATION_LAYERS is true). Vulkano handles that
+        // for us internally.
+
+        let (device, queues) = Device::new(*physical_device, &Features::none(),
+            &device_extensions(), queue_families)
+            .expect("failed to create logical device!");
+
+        // `queues` has exactly one queue per unique family requested above,
+        // so when graphics and present share a family (the common case)
+        // there's only one queue to hand out for both roles; collecting
+        // first lets us look it up by id without consuming it out from
+        // under the other lookup.
+        let queues: Vec<Arc<Queue>> = queues.collect();
+
+        // TODO!: simplify
+        let graphics_queue = queues.iter()
+            .find(|q| q.family().id() == physical_device.queue_families().nth(indices.graphics_family as usize).unwrap().id())
+            .expect("failed to find graphics queue!")
+            .clone();
+        let present_queue = queues.iter()
+            .find(|q| q.family().id() == physical_device.queue_families().nth(indices.present_family as usize).unwrap().id())
+            .expect("failed to find present queue!")
+            .clone();
+
+        (device, graphics_queue, present_queue)
+    }
+
+    fn choose_swap_surface_format(available_formats: &[(Format, ColorSpace)]) -> (Format, ColorSpace) {
+        // NOTE: the 'preferred format' mentioned in the tutorial doesn't seem to be
+        // queryable in Vulkano (no VK_FORMAT_UNDEFINED enum)
+        *available_formats.iter()
+            .find(|(format, color_space)|
+                *format == Format::B8G8R8A8Unorm && *color_space == ColorSpace::SrgbNonLinear
+            )
+            .unwrap_or_else(|| &available_formats[0])
+    }
+
+    fn choose_swap_present_mode(available_present_modes: SupportedPresentModes) -> PresentMode {
+        if available_present_modes.mailbox {
+            PresentMode::Mailbox
+        } else if available_present_modes.immediate {
+            PresentMode::Immediate
+        } else {
+            PresentMode::Fifo
+        }
+    }
+
+    fn choose_swap_extent(capabilities: &Capabilities, fallback_extent: [u32; 2]) -> [u32; 2] {
+        if let Some(current_extent) = capabilities.current_extent {
+            return current_extent
+        } else {
+            let mut actual_extent = fallback_extent;
+            actual_extent[0] = capabilities.min_image_extent[0]
+                .max(capabilities.max_image_extent[0].min(actual_extent[0]));
+            actual_extent[1] = capabilities.min_image_extent[1]
+                .max(capabilities.max_image_extent[1].min(actual_extent[1]));
+            actual_extent
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_swap_chain(
+        device: &Arc<Device>,
+        surface: &Arc<Surface<W>>,
+        physical_device: &PhysicalDevice,
+        indices: QueueFamilyIndices,
+        fallback_extent: [u32; 2],
+        graphics_queue: &Arc<Queue>,
+        present_queue: &Arc<Queue>,
+        old_swapchain: Option<Arc<Swapchain<W>>>,
+    ) -> (Arc<Swapchain<W>>, Vec<Arc<SwapchainImage<W>>>, Format, [u32; 2]) {
+        let capabilities = surface.capabilities(*physical_device)
+            .expect("failed to get surface capabilities");
+
+        let surface_format = Self::choose_swap_surface_format(&capabilities.supported_formats);
+        let present_mode = Self::choose_swap_present_mode(capabilities.present_modes);
+        let extent = Self::choose_swap_extent(&capabilities, fallback_extent);
+
+        let mut image_count = capabilities.min_image_count + 1;
+        if capabilities.max_image_count.is_some() && image_count > capabilities.max_image_count.unwrap() {
+            image_count = capabilities.max_image_count.unwrap();
+        }
+
+        let image_usage = ImageUsage {
+            color_attachment: true,
+            .. ImageUsage::none()
+        };
+
+        let sharing: SharingMode = if indices.graphics_family != indices.present_family {
+            vec![graphics_queue, present_queue].as_slice().into()
+        } else {
+            graphics_queue.into()
+        };
+
+        let (swap_chain, images) = Swapchain::new(
+            device.clone(),
+            surface.clone(),
+            image_count,
+            surface_format.0, // TODO!? (color space?)
+            extent,
+            1, // layers
+            image_usage,
+            sharing,
+            capabilities.current_transform,
+            CompositeAlpha::Opaque,
+            present_mode,
+            true, // clipped
+            old_swapchain,
+        ).expect("failed to create swap chain!");
+
+        println!("Swapchain created!");
+
+        (swap_chain, images, surface_format.0, extent)
+    }
+
+    /// Creates the per-frame-in-flight completion fences, pre-signaled so the
+    /// first `MAX_FRAMES_IN_FLIGHT` frames don't wait on work that never happened.
+    fn create_sync_objects(device: &Arc<Device>) -> Vec<Arc<FrameFuture>> {
+        (0..MAX_FRAMES_IN_FLIGHT)
+            .map(|_| {
+                let future = vulkano::sync::now(device.clone()).boxed()
+                    .then_signal_fence_and_flush()
+                    .expect("failed to signal initial fence!");
+                Arc::new(future)
+            })
+            .collect()
+    }
+
+    fn create_render_pass(
+        device: &Arc<Device>,
+        color_format: Format,
+        depth_format: Format,
+    ) -> Arc<dyn RenderPassAbstract + Send + Sync> {
+        Arc::new(single_pass_renderpass!(device.clone(),
+            attachments: {
+                color: {
+                    load: Clear,
+                    store: Store,
+                    format: color_format,
+                    samples: 1,
+                },
+                depth: {
+                    load: Clear,
+                    store: DontCare,
+                    format: depth_format,
+                    samples: 1,
+                }
+            },
+            pass: {
+                color: [color],
+                depth_stencil: {depth}
+            }
+        ).unwrap())
+    }
+
+    /// Picks the first of `candidates` whose optimal tiling supports being
+    /// used as a depth-stencil attachment.
+    fn find_supported_format(physical_device: &PhysicalDevice, candidates: &[Format]) -> Format {
+        *candidates.iter()
+            .find(|&&format| physical_device.format_properties(format).optimal_tiling_features.depth_stencil_attachment)
+            .expect("failed to find a supported depth format!")
+    }
+
+    fn find_depth_format(physical_device: &PhysicalDevice) -> Format {
+        Self::find_supported_format(physical_device, &[
+            Format::D32Sfloat,
+            Format::D32Sfloat_S8Uint,
+            Format::D24Unorm_S8Uint,
+        ])
+    }
+
+    fn create_depth_resources(device: &Arc<Device>, depth_format: Format, extent: [u32; 2]) -> Arc<AttachmentImage> {
+        AttachmentImage::transient(device.clone(), extent, depth_format)
+            .expect("failed to create depth image!")
+    }
+
+    fn create_graphics_pipeline(
+        device: &Arc<Device>,
+        extent: [u32; 2],
+        render_pass: &Arc<dyn RenderPassAbstract + Send + Sync>,
+    ) -> Arc<ConcreteGraphicsPipeline> {
+        let vert_shader_module = vertex_shader::Shader::load(device.clone())
+            .expect("failed to create vertex shader module!");
+        let frag_shader_module = fragment_shader::Shader::load(device.clone())
+            .expect("failed to create fragment shader module!");
+
+        let viewport = Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [extent[0] as f32, extent[1] as f32],
+            depth_range: 0.0 .. 1.0,
+        };
+
+        Arc::new(GraphicsPipeline::start()
+            .vertex_input(BufferlessDefinition {})
+            .vertex_shader(vert_shader_module.main_entry_point(), ())
+            .triangle_list()
+            .primitive_restart(false)
+            .viewports(vec![viewport])
+            .fragment_shader(frag_shader_module.main_entry_point(), ())
+            .depth_clamp(false)
+            .polygon_mode_fill()
+            .line_width(1.0)
+            .cull_mode_back()
+            .front_face_clockwise()
+            .blend_pass_through()
+            .depth_stencil_simple_depth()
+            .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+            .build(device.clone())
+            .unwrap())
+    }
+
+    fn create_framebuffers(
+        render_pass: &Arc<dyn RenderPassAbstract + Send + Sync>,
+        swap_chain_images: &[Arc<SwapchainImage<W>>],
+        depth_image: &Arc<AttachmentImage>,
+    ) -> Vec<Arc<dyn FramebufferAbstract + Send + Sync>> {
+        swap_chain_images.iter()
+            .map(|image| {
+                let framebuffer: Arc<dyn FramebufferAbstract + Send + Sync> = Arc::new(
+                    Framebuffer::start(render_pass.clone())
+                        .add(image.clone()).unwrap()
+                        .add(depth_image.clone()).unwrap()
+                        .build().unwrap()
+                );
+                framebuffer
+            })
+            .collect()
+    }
+
+    fn create_command_buffers(
+        device: &Arc<Device>,
+        graphics_queue: &Arc<Queue>,
+        pipeline: &Arc<ConcreteGraphicsPipeline>,
+        framebuffers: &[Arc<dyn FramebufferAbstract + Send + Sync>],
+    ) -> Vec<Arc<AutoCommandBuffer>> {
+        framebuffers.iter()
+            .map(|framebuffer| {
+                let vertices = BufferlessVertices { vertices: 3, instances: 1 };
+                Arc::new(AutoCommandBufferBuilder::primary_simultaneous_use(device.clone(), graphics_queue.family())
+                    .unwrap()
+                    .begin_render_pass(framebuffer.clone(), false, vec![[0.0, 0.0, 0.0, 1.0].into(), 1.0.into()])
+                    .unwrap()
+                    .draw(pipeline.clone(), &DynamicState::none(), vertices, (), ())
+                    .unwrap()
+                    .end_render_pass()
+                    .unwrap()
+                    .build()
+                    .unwrap())
+            })
+            .collect()
+    }
+
+    fn find_queue_families(surface: &Surface<W>, device: &PhysicalDevice) -> QueueFamilyIndices {
+        let mut indices = QueueFamilyIndices::new();
+        // TODO: replace index with id to simplify?
+        for (i, queue_family) in device.queue_families().enumerate() {
+            if indices.graphics_family < 0 && queue_family.supports_graphics() {
+                indices.graphics_family = i as i32;
+            }
+
+            if indices.present_family < 0 && surface.is_supported(queue_family).unwrap_or(false) {
+                indices.present_family = i as i32;
+            }
+
+            if indices.is_complete() {
+                break;
+            }
+        }
+
+        indices
+    }
+
+    fn check_validation_layer_support() -> bool {
+        for layer_name in VALIDATION_LAYERS.iter() {
+            let mut layer_found = false;
+            for layer_properties in layers_list().unwrap() {
+                if *layer_name == layer_properties.name() {
+                    layer_found = true;
+                    break
+                }
+            }
+            if !layer_found {
+                return false;
+            }
+        }
+
+        true
+    }
+}